@@ -2,7 +2,7 @@
 
 use std::error::Error as StdError;
 use std::fs::File;
-use std::io::{BufReader, Write, stdout};
+use std::io::{BufRead, BufReader, Read, Write, stdout};
 use std::collections::{HashMap, HashSet};
 use std::str;
 use std::path::Path;
@@ -15,6 +15,8 @@ use uuid::Uuid;
 use blake2b_simd::Params;
 use pbr::ProgressBar;
 use bzip2::bufread::BzDecoder;
+use flate2::bufread::GzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
 use quick_xml::{Reader, events::Event};
 use regex::Regex;
 use serde::{Serialize, Deserialize};
@@ -134,12 +136,56 @@ enum ArchiveReadState {
     Text,
 }
 
-async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
-    let mut article_map = ArticleMap::default();
+/// Sniffs the leading magic bytes of `f` to detect its compression codec and wraps it
+/// in the matching decoder, so the archive no longer has to be bzip2. Falls back to
+/// treating the contents as plain, uncompressed XML.
+fn wrap_decompressor(f: File) -> Result<Box<dyn BufRead>, Box<dyn StdError>> {
+    let mut f = BufReader::new(f);
+    let magic = f.fill_buf()?;
+
+    Ok(if magic.starts_with(b"BZh") {
+        Box::new(BufReader::new(BzDecoder::new(f)))
+    } else if magic.starts_with(&[0x1f, 0x8b]) {
+        Box::new(BufReader::new(GzDecoder::new(f)))
+    } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+        Box::new(BufReader::new(ZstdDecoder::new(f)?))
+    } else {
+        Box::new(f)
+    })
+}
+
+/// Pushes the vertex + name property for `name` into `inserter`, unless `seen` already
+/// has it. Returns the article's UUID either way, so callers can use it for edges
+/// without caring whether the vertex insert was just deduped.
+async fn ensure_article(
+    inserter: &mut BulkInserter,
+    seen: &mut HashSet<Uuid>,
+    article_type: &indradb::Type,
+    name: &str,
+) -> Uuid {
+    let uuid = article_uuid(name);
+
+    if seen.insert(uuid) {
+        inserter.push(indradb::BulkInsertItem::Vertex(indradb::Vertex::with_id(uuid, article_type.clone()))).await;
+        inserter.push(indradb::BulkInsertItem::VertexProperty(uuid, "name".to_string(), JsonValue::String(name.to_string()))).await;
+    }
+
+    uuid
+}
+
+/// Parses the archive and inserts each page's vertex and outgoing `link` edges as
+/// soon as it's parsed, rather than first building the full graph in memory. Since
+/// article UUIDs are deterministic hashes of the title, the only state that needs to
+/// persist across pages is `seen`, which dedupes vertex inserts for titles that were
+/// already encountered as a link target before their own page was reached.
+async fn read_archive(f: File, client: proto::Client) -> Result<(), Box<dyn StdError>> {
+    let mut inserter = BulkInserter::new(client);
+    let mut seen = HashSet::<Uuid>::new();
+    let article_type = indradb::Type::new("article").unwrap();
+    let link_type = indradb::Type::new("link").unwrap();
 
     let mut buf = Vec::new();
-    let f = BufReader::new(f);
-    let decompressor = BufReader::new(BzDecoder::new(f));
+    let decompressor = wrap_decompressor(f)?;
     let mut reader = Reader::from_reader(decompressor);
     reader.trim_text(true);
     reader.check_end_names(false);
@@ -152,7 +198,7 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
     let title_tag = "title".as_bytes();
     let text_tag = "text".as_bytes();
     let revision_tag = "revision".as_bytes();
-    let mut last_article_map_len = 0;
+    let mut last_seen_len = 0;
 
     let wiki_link_re = Regex::new(r"\[\[([^\[\]|]+)(|[\]]+)?\]\]").unwrap();
 
@@ -180,11 +226,11 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
                 debug_assert!(!src.is_empty());
                 debug_assert!(!content.is_empty());
 
-                let src_uuid = article_map.insert_article(&src);
+                let src_uuid = ensure_article(&mut inserter, &mut seen, &article_type, &src).await;
                 for cap in wiki_link_re.captures_iter(&content) {
                     let dst = &cap[1];
-                    let dst_uuid = article_map.insert_article(dst);
-                    article_map.insert_link(src_uuid, dst_uuid);
+                    let dst_uuid = ensure_article(&mut inserter, &mut seen, &article_type, dst).await;
+                    inserter.push(indradb::BulkInsertItem::Edge(indradb::EdgeKey::new(src_uuid, link_type.clone(), dst_uuid))).await;
                 }
 
                 ArchiveReadState::Ignore
@@ -227,30 +273,63 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
 
         buf.clear();
 
-        if article_map.uuids.len() - last_article_map_len >= 1000 {
-            last_article_map_len = article_map.uuids.len();
-            print!("\rreading archive: {}", last_article_map_len);
+        if seen.len() - last_seen_len >= 1000 {
+            last_seen_len = seen.len();
+            print!("\rreading archive: {}", last_seen_len);
             stdout().flush()?;
         }
     }
 
     println!("\rreading archive: done");
 
-    Ok(article_map)
+    inserter.flush().await;
+    Ok(())
 }
 
-async fn load_article_map(input_filepath: &str, dump_filepath: &str) -> Result<ArticleMap, Box<dyn StdError>> {
-    if Path::new(dump_filepath).exists() {
-        print!("reading dump...");
-        stdout().flush()?;
-        let article_map = bincode::deserialize_from(File::open(dump_filepath)?)?;
-        println!("\rreading dump: done");
-        Ok(article_map)
-    } else {
-        let article_map = read_archive(File::open(input_filepath)?).await?;
-        bincode::serialize_into(File::create(dump_filepath)?, &article_map)?;
-        Ok(article_map)
+// Bumped whenever the on-disk shape of `ArticleMap` or its UUID scheme changes, so an
+// old dump (e.g. from before the blake2b hashing switch) is never silently misread.
+const CACHE_MAGIC: [u8; 4] = *b"WAMC";
+const CURRENT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct CacheHeader {
+    magic: [u8; 4],
+    format_version: u32,
+}
+
+/// Upgrades a dump written by an older format version. No migrations exist yet, so
+/// this just errors clearly; a future format bump can add a branch here to upgrade
+/// old caches in place rather than forcing a full re-parse of the archive.
+fn migrate(old_version: u32, _reader: impl Read) -> Result<ArticleMap, Box<dyn StdError>> {
+    Err(format!(
+        "article map cache is format version {}, but this build only knows how to read version {} (no migration path yet)",
+        old_version, CURRENT_VERSION
+    ).into())
+}
+
+/// Loads a cached `ArticleMap` dump, if one exists. There's no equivalent write path
+/// any more: a fresh run streams straight from the archive into the database (see
+/// `read_archive`) precisely to avoid ever holding the full graph in memory, and
+/// building a dump would require doing exactly that.
+fn load_cached_article_map(dump_filepath: &str) -> Result<Option<ArticleMap>, Box<dyn StdError>> {
+    if !Path::new(dump_filepath).exists() {
+        return Ok(None);
+    }
+
+    print!("reading dump...");
+    stdout().flush()?;
+    let mut f = File::open(dump_filepath)?;
+    let header: CacheHeader = bincode::deserialize_from(&mut f)?;
+    if header.magic != CACHE_MAGIC {
+        return Err(format!("{} is not a recognized article map cache", dump_filepath).into());
     }
+    let article_map = if header.format_version == CURRENT_VERSION {
+        bincode::deserialize_from(f)?
+    } else {
+        migrate(header.format_version, f)?
+    };
+    println!("\rreading dump: done");
+    Ok(Some(article_map))
 }
 
 async fn insert_articles(client: proto::Client, article_map: &ArticleMap) -> Result<(), proto::ClientError> {
@@ -313,12 +392,19 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
         .get_matches();
 
     task::LocalSet::new().run_until(async move {
-        let article_map = load_article_map(
-            matches.value_of("ARCHIVE_INPUT").unwrap(),
-            matches.value_of("ARCHIVE_DUMP").unwrap(),
-        ).await?;
-        insert_articles(build_client().await?, &article_map).await.map_err(|err| err.compat())?;
-        insert_links(build_client().await?, &article_map).await.map_err(|err| err.compat())?;
+        let dump_filepath = matches.value_of("ARCHIVE_DUMP").unwrap();
+
+        match load_cached_article_map(dump_filepath)? {
+            Some(article_map) => {
+                insert_articles(build_client().await?, &article_map).await.map_err(|err| err.compat())?;
+                insert_links(build_client().await?, &article_map).await.map_err(|err| err.compat())?;
+            }
+            None => {
+                let input_filepath = matches.value_of("ARCHIVE_INPUT").unwrap();
+                read_archive(File::open(input_filepath)?, build_client().await?).await?;
+            }
+        }
+
         Ok(())
     }).await
 }
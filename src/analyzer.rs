@@ -1,21 +1,151 @@
+use std::collections::HashMap;
 use std::error::Error as StdError;
+use std::ffi::OsStr;
+use std::mem::replace;
 use std::time::Instant;
 
 use indradb_proto as proto;
-use serde_json::json;
+use pbr::ProgressBar;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
 
-pub async fn run(mut client: proto::Client) -> Result<(), Box<dyn StdError>> {
+use crate::indexer::{dump_path, read_dump, ArticleMap};
+
+const REQUEST_BUFFER_SIZE: usize = 10_000;
+
+const DAMPING_FACTOR: f64 = 0.85;
+const CONVERGENCE_TOLERANCE: f64 = 1e-6;
+const MAX_ITERATIONS: u32 = 100;
+
+struct BulkInserter {
+    requests: async_channel::Sender<Vec<indradb::BulkInsertItem>>,
+    workers: Vec<JoinHandle<()>>,
+    buf: Vec<indradb::BulkInsertItem>,
+}
+
+impl BulkInserter {
+    fn new(client: proto::Client) -> Self {
+        let (tx, rx) = async_channel::bounded::<Vec<indradb::BulkInsertItem>>(10);
+        let mut workers = Vec::default();
+
+        for _ in 0..10 {
+            let rx = rx.clone();
+            let mut client = client.clone();
+            workers.push(tokio::spawn(async move {
+                while let Ok(buf) = rx.recv().await {
+                    client.bulk_insert(buf).await.unwrap();
+                }
+            }));
+        }
+
+        Self {
+            requests: tx,
+            workers,
+            buf: Vec::with_capacity(REQUEST_BUFFER_SIZE),
+        }
+    }
+
+    async fn flush(self) {
+        if !self.buf.is_empty() {
+            self.requests.send(self.buf).await.unwrap();
+        }
+        self.requests.close();
+        for worker in self.workers.into_iter() {
+            worker.await.unwrap();
+        }
+    }
+
+    async fn push(&mut self, item: indradb::BulkInsertItem) {
+        self.buf.push(item);
+        if self.buf.len() >= REQUEST_BUFFER_SIZE {
+            let buf = replace(&mut self.buf, Vec::with_capacity(REQUEST_BUFFER_SIZE));
+            self.requests.send(buf).await.unwrap();
+        }
+    }
+}
+
+/// Computes PageRank over `article_map.links`, with damping `DAMPING_FACTOR` and
+/// dangling nodes (zero out-degree) redistributing their mass uniformly across every
+/// node each iteration. Stops once the L1 delta between iterations falls below
+/// `CONVERGENCE_TOLERANCE`, or after `MAX_ITERATIONS`.
+fn page_rank(article_map: &ArticleMap) -> HashMap<Uuid, f64> {
+    let uuids: Vec<Uuid> = article_map.uuids.values().copied().collect();
+    let n = uuids.len() as f64;
+
+    let out_degree: HashMap<Uuid, usize> = uuids.iter().map(|&uuid| {
+        let degree = article_map.links.get(&uuid).map(|dsts| dsts.len()).unwrap_or(0);
+        (uuid, degree)
+    }).collect();
+
+    let mut reverse_links: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for (&src_uuid, dst_uuids) in &article_map.links {
+        for &dst_uuid in dst_uuids {
+            reverse_links.entry(dst_uuid).or_insert_with(Vec::new).push(src_uuid);
+        }
+    }
+
+    let mut rank: HashMap<Uuid, f64> = uuids.iter().map(|&uuid| (uuid, 1.0 / n)).collect();
+
+    for _ in 0..MAX_ITERATIONS {
+        let dangling_mass: f64 = uuids
+            .iter()
+            .filter(|uuid| out_degree[uuid] == 0)
+            .map(|uuid| rank[uuid])
+            .sum();
+
+        let mut next_rank = HashMap::with_capacity(uuids.len());
+        let mut delta = 0.0;
+
+        for &uuid in &uuids {
+            let inbound: f64 = reverse_links
+                .get(&uuid)
+                .map(|srcs| srcs.iter().map(|src_uuid| rank[src_uuid] / out_degree[src_uuid] as f64).sum())
+                .unwrap_or(0.0);
+
+            let new_rank = (1.0 - DAMPING_FACTOR) / n + DAMPING_FACTOR * (inbound + dangling_mass / n);
+            delta += (new_rank - rank[&uuid]).abs();
+            next_rank.insert(uuid, new_rank);
+        }
+
+        rank = next_rank;
+
+        if delta < CONVERGENCE_TOLERANCE {
+            break;
+        }
+    }
+
+    rank
+}
+
+pub async fn run(mut client: proto::Client, archive_path: &OsStr) -> Result<(), Box<dyn StdError>> {
     let start_time = Instant::now();
-    client
-        .execute_plugin(
-            "centrality",
-            json!({
-                "max_iterations": 50,
-                "cache_edges": true,
-                "max_delta": 0.015
-            }),
-        )
-        .await?;
+
+    let article_map = read_dump(&dump_path(archive_path))?;
+    let rank = page_rank(&article_map);
+
+    let rank_identifier = indradb::Identifier::new("rank")?;
+    client.index_property(rank_identifier).await?;
+
+    let mut progress = ProgressBar::new(rank.len() as u64);
+    progress.message("writing ranks: ");
+
+    let mut inserter = BulkInserter::new(client);
+
+    for (uuid, score) in rank {
+        inserter
+            .push(indradb::BulkInsertItem::VertexProperty(
+                uuid,
+                rank_identifier,
+                indradb::Json::new(serde_json::json!(score)),
+            ))
+            .await;
+        progress.inc();
+    }
+
+    inserter.flush().await;
+    progress.finish();
+    println!();
+
     println!("finished in {} seconds", start_time.elapsed().as_secs());
     Ok(())
 }
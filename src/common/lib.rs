@@ -1,13 +1,17 @@
 #[macro_use] extern crate lazy_static;
 
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::mem::replace;
 use std::process::{Command, Child};
 use std::convert::TryInto;
+use std::time::Duration;
 
 use indradb_proto as proto;
 use blake2b_simd::Params;
 use uuid::Uuid;
 use tonic::transport::Endpoint;
+use tokio::time::sleep;
 
 const PORT: u16 = 27615;
 
@@ -29,14 +33,87 @@ pub async fn client() -> Result<proto::Client, proto::ClientError> {
     proto::Client::new(endpoint).await
 }
 
+/// Redirect chains are followed at most this many hops before giving up, so a cycle
+/// (or a pathologically long chain) can't hang resolution.
+pub const MAX_REDIRECT_DEPTH: usize = 8;
+
+/// Follows a redirect chain from `uuid` to its canonical target, stopping at
+/// `MAX_REDIRECT_DEPTH` hops or as soon as a cycle is detected.
+///
+/// Shared by `indexer::ArticleMap` and `crawler::ArticleMap`, which each maintain
+/// their own `redirects: HashMap<Uuid, Uuid>` but otherwise resolve them identically.
+pub fn resolve_redirect_target(redirects: &HashMap<Uuid, Uuid>, mut uuid: Uuid) -> Uuid {
+    let mut visited = HashSet::new();
+    for _ in 0..MAX_REDIRECT_DEPTH {
+        if !visited.insert(uuid) {
+            break;
+        }
+        match redirects.get(&uuid) {
+            Some(&target) => uuid = target,
+            None => break,
+        }
+    }
+    uuid
+}
+
+/// Rewrites every value in `links` that is itself a redirect to point at its
+/// resolved, canonical target, following chains. `redirects` itself is left
+/// untouched so the original redirect edges can still be queried explicitly.
+pub fn resolve_redirects(links: &mut HashMap<Uuid, HashSet<Uuid>>, redirects: &HashMap<Uuid, Uuid>) {
+    if redirects.is_empty() {
+        return;
+    }
+
+    let old_links = replace(links, HashMap::default());
+    for (src_uuid, dst_uuids) in old_links {
+        let resolved = links.entry(src_uuid).or_insert_with(HashSet::default);
+        for dst_uuid in dst_uuids {
+            resolved.insert(resolve_redirect_target(redirects, dst_uuid));
+        }
+    }
+}
+
+pub async fn retrying_client() -> Result<proto::Client, proto::ClientError> {
+    let mut last_err: Option<proto::ClientError> = None;
+
+    for _ in 0..5 {
+        match client().await {
+            Ok(client) => return Ok(client),
+            Err(err) => last_err = Some(err),
+        }
+        sleep(Duration::from_secs(1)).await;
+    }
+
+    Err(last_err.unwrap())
+}
+
+/// The embedded storage backend `indradb` should run against. Both variants speak the
+/// same gRPC protocol, so `client()`/`retrying_client()` work unchanged either way —
+/// only the server process's own args/env differ.
+pub enum Backend {
+    Rocksdb { path: String },
+    Sled { url: String },
+}
+
 pub struct Server(Child);
 
 impl Server {
-    pub fn start(database_path: &str) -> Result<Self, Box<dyn Error>> {
-        let child = Command::new("indradb")
-            .args(&["rocksdb", database_path, "--compression", "true"])
-            .env("RUST_BACKTRACE", "1")
-            .spawn()?;
+    pub fn start(backend: Backend) -> Result<Self, Box<dyn Error>> {
+        let child = match backend {
+            Backend::Rocksdb { path } => {
+                Command::new("indradb")
+                    .args(&["rocksdb", &path, "--compression", "true"])
+                    .env("RUST_BACKTRACE", "1")
+                    .spawn()?
+            }
+            Backend::Sled { url } => {
+                Command::new("indradb")
+                    .env("DATABASE_URL", url)
+                    .env("RUST_BACKTRACE", "1")
+                    .env("SLEDDB_COMPRESSION", "true")
+                    .spawn()?
+            }
+        };
 
         Ok(Self { 0: child })
     }
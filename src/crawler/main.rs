@@ -1,13 +1,16 @@
 use std::error::Error as StdError;
+use std::ffi::OsStr;
 use std::fs::File;
-use std::io::{BufReader, Write, stdout};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write, stdout};
 use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
 use std::str;
 use std::path::Path;
 use std::mem::replace;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 
-use failure::Fail;
-use indradb_proto as proto;
 use serde_json::value::Value as JsonValue;
 use uuid::Uuid;
 use pbr::ProgressBar;
@@ -17,6 +20,9 @@ use regex::Regex;
 use serde::{Serialize, Deserialize};
 use tokio::task::JoinHandle;
 use clap::{App, Arg};
+use warp::Filter;
+use fst::automaton::{Automaton, Levenshtein, Str};
+use fst::{IntoStreamer, Map as FstMap, MapBuilder, Streamer};
 
 const REQUEST_BUFFER_SIZE: usize = 10_000;
 
@@ -32,24 +38,150 @@ const ARTICLE_NAME_PREFIX_BLACKLIST: [&str; 7] = [
 
 const REDIRECT_PREFIX: &str = "#REDIRECT [[";
 
+/// Ingestion-progress counters and gauges, exposed in Prometheus text format so a
+/// multi-hour headless dump load can be watched from outside the process.
+#[derive(Default)]
+struct Metrics {
+    articles_parsed_total: AtomicU64,
+    links_extracted_total: AtomicU64,
+    vertices_inserted_total: AtomicU64,
+    edges_inserted_total: AtomicU64,
+    batches_in_flight: AtomicI64,
+    bytes_decompressed_total: AtomicU64,
+}
+
+impl Metrics {
+    fn render(&self) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP wikipedia_crawler_articles_parsed_total Articles parsed from the archive.\n");
+        out.push_str("# TYPE wikipedia_crawler_articles_parsed_total counter\n");
+        out.push_str(&format!("wikipedia_crawler_articles_parsed_total {}\n", self.articles_parsed_total.load(Ordering::Relaxed)));
+
+        out.push_str("# HELP wikipedia_crawler_links_extracted_total Links extracted from parsed articles.\n");
+        out.push_str("# TYPE wikipedia_crawler_links_extracted_total counter\n");
+        out.push_str(&format!("wikipedia_crawler_links_extracted_total {}\n", self.links_extracted_total.load(Ordering::Relaxed)));
+
+        out.push_str("# HELP wikipedia_crawler_vertices_inserted_total Vertices bulk-inserted into IndraDB.\n");
+        out.push_str("# TYPE wikipedia_crawler_vertices_inserted_total counter\n");
+        out.push_str(&format!("wikipedia_crawler_vertices_inserted_total {}\n", self.vertices_inserted_total.load(Ordering::Relaxed)));
+
+        out.push_str("# HELP wikipedia_crawler_edges_inserted_total Edges bulk-inserted into IndraDB.\n");
+        out.push_str("# TYPE wikipedia_crawler_edges_inserted_total counter\n");
+        out.push_str(&format!("wikipedia_crawler_edges_inserted_total {}\n", self.edges_inserted_total.load(Ordering::Relaxed)));
+
+        out.push_str("# HELP wikipedia_crawler_batches_in_flight Bulk-insert batches sent but not yet acknowledged.\n");
+        out.push_str("# TYPE wikipedia_crawler_batches_in_flight gauge\n");
+        out.push_str(&format!("wikipedia_crawler_batches_in_flight {}\n", self.batches_in_flight.load(Ordering::Relaxed)));
+
+        out.push_str("# HELP wikipedia_crawler_bytes_decompressed_total Bytes read from the bzip2 archive stream.\n");
+        out.push_str("# TYPE wikipedia_crawler_bytes_decompressed_total counter\n");
+        out.push_str(&format!("wikipedia_crawler_bytes_decompressed_total {}\n", self.bytes_decompressed_total.load(Ordering::Relaxed)));
+
+        out
+    }
+}
+
+/// Wraps a reader to tally bytes passing through it into `Metrics::bytes_decompressed_total`.
+struct CountingReader<R> {
+    inner: R,
+    metrics: Arc<Metrics>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.metrics.bytes_decompressed_total.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+async fn serve_metrics(addr: SocketAddr, metrics: Arc<Metrics>) {
+    let route = warp::path("metrics").map(move || metrics.render());
+    warp::serve(route).run(addr).await;
+}
+
+/// Bulk-insert batches are attempted up to this many times in total (mirroring
+/// `common::retrying_client`'s attempt count) before a worker gives up on a batch.
+const MAX_BULK_INSERT_ATTEMPTS: u32 = 5;
+
+/// Reports bulk-insert batches that a worker couldn't commit even after retrying and
+/// reconnecting, so partial failures during a multi-hour load are visible rather than
+/// panicking the whole process.
+#[derive(Debug)]
+struct BulkInsertError {
+    lost_batches: usize,
+    lost_items: usize,
+}
+
+impl std::fmt::Display for BulkInsertError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "bulk insert failed: lost {} batch(es) totaling {} item(s) after exhausting retries",
+            self.lost_batches, self.lost_items
+        )
+    }
+}
+
+impl StdError for BulkInsertError {}
+
 struct BulkInserter {
     requests: async_channel::Sender<Vec<indradb::BulkInsertItem>>,
-    workers: Vec<JoinHandle<()>>,
-    buf: Vec<indradb::BulkInsertItem>
+    workers: Vec<JoinHandle<(usize, usize)>>,
+    buf: Vec<indradb::BulkInsertItem>,
+    metrics: Arc<Metrics>,
 }
 
-impl Default for BulkInserter {
-    fn default() -> Self {
+impl BulkInserter {
+    fn new(metrics: Arc<Metrics>) -> Self {
         let (tx, rx) = async_channel::bounded::<Vec<indradb::BulkInsertItem>>(10);
         let mut workers = Vec::default();
 
         for _ in 0..10 {
             let rx = rx.clone();
+            let worker_metrics = metrics.clone();
             workers.push(tokio::spawn(async move {
                 let mut client = common::client().await.unwrap();
+                let mut lost_batches = 0;
+                let mut lost_items = 0;
+
                 while let Ok(buf) = rx.recv().await {
-                    client.bulk_insert(buf.into_iter()).await.unwrap();
+                    let mut committed = false;
+                    let mut last_err = None;
+
+                    for attempt in 0..MAX_BULK_INSERT_ATTEMPTS {
+                        match client.bulk_insert(buf.clone().into_iter()).await {
+                            Ok(()) => {
+                                committed = true;
+                                break;
+                            }
+                            Err(err) => {
+                                last_err = Some(err);
+                                if attempt + 1 < MAX_BULK_INSERT_ATTEMPTS {
+                                    tokio::time::sleep(Duration::from_secs(1 << attempt)).await;
+                                    if let Ok(reconnected) = common::client().await {
+                                        client = reconnected;
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    worker_metrics.batches_in_flight.fetch_sub(1, Ordering::Relaxed);
+
+                    if !committed {
+                        eprintln!(
+                            "bulk insert batch dropped after {} attempts: {}",
+                            MAX_BULK_INSERT_ATTEMPTS,
+                            last_err.unwrap()
+                        );
+                        lost_batches += 1;
+                        lost_items += buf.len();
+                    }
                 }
+
+                (lost_batches, lost_items)
             }));
         }
 
@@ -57,25 +189,47 @@ impl Default for BulkInserter {
             requests: tx,
             workers,
             buf: Vec::with_capacity(REQUEST_BUFFER_SIZE),
+            metrics,
         }
     }
-}
 
-impl BulkInserter {
-    async fn flush(self) {
+    async fn flush(self) -> Result<(), BulkInsertError> {
         if !self.buf.is_empty() {
+            self.metrics.batches_in_flight.fetch_add(1, Ordering::Relaxed);
             self.requests.send(self.buf).await.unwrap();
         }
         self.requests.close();
+
+        let mut lost_batches = 0;
+        let mut lost_items = 0;
         for worker in self.workers.into_iter() {
-            worker.await.unwrap();
+            let (worker_lost_batches, worker_lost_items) = worker.await.unwrap();
+            lost_batches += worker_lost_batches;
+            lost_items += worker_lost_items;
+        }
+
+        if lost_batches > 0 {
+            Err(BulkInsertError { lost_batches, lost_items })
+        } else {
+            Ok(())
         }
     }
 
     async fn push(&mut self, item: indradb::BulkInsertItem) {
+        match &item {
+            indradb::BulkInsertItem::Vertex(_) => {
+                self.metrics.vertices_inserted_total.fetch_add(1, Ordering::Relaxed);
+            }
+            indradb::BulkInsertItem::Edge(_) => {
+                self.metrics.edges_inserted_total.fetch_add(1, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+
         self.buf.push(item);
         if self.buf.len() >= REQUEST_BUFFER_SIZE {
             let buf = replace(&mut self.buf, Vec::with_capacity(REQUEST_BUFFER_SIZE));
+            self.metrics.batches_in_flight.fetch_add(1, Ordering::Relaxed);
             self.requests.send(buf).await.unwrap();
         }
     }
@@ -85,7 +239,8 @@ impl BulkInserter {
 #[derive(Serialize, Deserialize)]
 struct ArticleMap {
     uuids: HashMap<String, Uuid>,
-    links: HashMap<Uuid, HashSet<Uuid>>
+    links: HashMap<Uuid, HashSet<Uuid>>,
+    redirects: HashMap<Uuid, Uuid>,
 }
 
 impl Default for ArticleMap {
@@ -93,6 +248,7 @@ impl Default for ArticleMap {
         Self {
             uuids: HashMap::default(),
             links: HashMap::default(),
+            redirects: HashMap::default(),
         }
     }
 }
@@ -112,6 +268,107 @@ impl ArticleMap {
         let container = self.links.entry(src_uuid).or_insert_with(HashSet::default);
         container.insert(dst_uuid);
     }
+
+    fn insert_redirect(&mut self, src_uuid: Uuid, dst_uuid: Uuid) {
+        self.redirects.insert(src_uuid, dst_uuid);
+    }
+
+    /// Unions `other` into `self`. Safe to call on partial maps produced by independent
+    /// multistream workers, since `common::article_uuid` is a deterministic hash of the
+    /// article name — identical titles collide on the same UUID with no coordination.
+    fn merge(&mut self, other: ArticleMap) {
+        self.uuids.extend(other.uuids);
+        for (src_uuid, dst_uuids) in other.links {
+            self.links.entry(src_uuid).or_insert_with(HashSet::default).extend(dst_uuids);
+        }
+        self.redirects.extend(other.redirects);
+    }
+
+    /// Rewrites every `link` destination that is itself a redirect to point at its
+    /// resolved, canonical target, following chains. The `redirect` edges themselves
+    /// are left untouched so they can still be queried explicitly. Delegates to
+    /// `common::resolve_redirects`, shared with `indexer::ArticleMap`.
+    fn resolve_redirects(&mut self) {
+        common::resolve_redirects(&mut self.links, &self.redirects);
+    }
+}
+
+/// A prefix- and typo-tolerant index over article names, backed by an `fst::Map`
+/// (which requires lexicographically sorted keys) plus a side table resolving each
+/// name back to its full 16-byte UUID, since the FST itself can only carry a compact
+/// `u64` surrogate value.
+#[derive(Serialize, Deserialize)]
+struct SearchIndex {
+    fst_bytes: Vec<u8>,
+    uuids_by_name: HashMap<String, Uuid>,
+}
+
+impl SearchIndex {
+    fn map(&self) -> FstMap<&[u8]> {
+        FstMap::new(self.fst_bytes.as_slice()).expect("corrupt search index")
+    }
+
+    /// Returns up to `limit` `(name, uuid)` pairs whose name starts with `prefix`.
+    fn search_prefix(&self, prefix: &str, limit: usize) -> Vec<(String, Uuid)> {
+        let automaton = Str::new(prefix).starts_with();
+        let mut stream = self.map().search(automaton).into_stream();
+
+        let mut out = Vec::new();
+        while let Some((name, _surrogate)) = stream.next() {
+            if out.len() >= limit {
+                break;
+            }
+            if let Ok(name) = str::from_utf8(name) {
+                if let Some(&uuid) = self.uuids_by_name.get(name) {
+                    out.push((name.to_string(), uuid));
+                }
+            }
+        }
+        out
+    }
+
+    /// Returns up to `limit` `(name, uuid)` pairs within `edit_distance` of `query`,
+    /// via a Levenshtein automaton intersected with the FST's transducer.
+    fn search_fuzzy(&self, query: &str, edit_distance: u32, limit: usize) -> Result<Vec<(String, Uuid)>, Box<dyn StdError>> {
+        let automaton = Levenshtein::new(query, edit_distance)?;
+        let mut stream = self.map().search(automaton).into_stream();
+
+        let mut out = Vec::new();
+        while let Some((name, _surrogate)) = stream.next() {
+            if out.len() >= limit {
+                break;
+            }
+            if let Ok(name) = str::from_utf8(name) {
+                if let Some(&uuid) = self.uuids_by_name.get(name) {
+                    out.push((name.to_string(), uuid));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Builds a `SearchIndex` over every article name in `article_map`. Names are sorted
+/// first since `fst::MapBuilder` requires keys to be inserted in lexicographic order.
+fn build_search_index(article_map: &ArticleMap) -> Result<SearchIndex, Box<dyn StdError>> {
+    let mut names: Vec<&String> = article_map.uuids.keys().collect();
+    names.sort();
+
+    let mut builder = MapBuilder::memory();
+    for (i, name) in names.iter().enumerate() {
+        builder.insert(name.as_bytes(), i as u64)?;
+    }
+    let fst_bytes = builder.into_inner()?;
+
+    Ok(SearchIndex {
+        fst_bytes,
+        uuids_by_name: article_map.uuids.clone(),
+    })
+}
+
+/// Derives the on-disk path for the FST search index dump from the archive dump path.
+fn search_index_path(dump_filepath: &str) -> String {
+    format!("{}.fst", dump_filepath)
 }
 
 enum ArchiveReadState {
@@ -122,12 +379,13 @@ enum ArchiveReadState {
     Text,
 }
 
-async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
+/// Runs the quick-xml state machine over an already-decompressed `<mediawiki>` stream,
+/// producing an `ArticleMap`. Shared by the whole-archive path and by each multistream
+/// worker's per-block decode.
+fn parse_pages<R: BufRead>(decompressor: R, metrics: &Metrics, report_progress: bool) -> Result<ArticleMap, Box<dyn StdError>> {
     let mut article_map = ArticleMap::default();
 
     let mut buf = Vec::new();
-    let f = BufReader::new(f);
-    let decompressor = BufReader::new(BzDecoder::new(f));
     let mut reader = Reader::from_reader(decompressor);
     reader.trim_text(true);
     reader.check_end_names(false);
@@ -144,8 +402,10 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
 
     let wiki_link_re = Regex::new(r"\[\[([^\[\]|]+)(|[\]]+)?\]\]").unwrap();
 
-    print!("reading archive: 0");
-    stdout().flush()?;
+    if report_progress {
+        print!("reading archive: 0");
+        stdout().flush()?;
+    }
 
     loop {
         state = match (state, reader.read_event(&mut buf)?) {
@@ -173,7 +433,9 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
                     let dst = &cap[1];
                     let dst_uuid = article_map.insert_article(dst);
                     article_map.insert_link(src_uuid, dst_uuid);
+                    metrics.links_extracted_total.fetch_add(1, Ordering::Relaxed);
                 }
+                metrics.articles_parsed_total.fetch_add(1, Ordering::Relaxed);
 
                 ArchiveReadState::Ignore
             },
@@ -198,9 +460,12 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
                 debug_assert!(content.is_empty());
                 content.push_str(str::from_utf8(e)?);
 
-                let blacklisted = content.starts_with(REDIRECT_PREFIX);
-
-                if blacklisted {
+                if content.starts_with(REDIRECT_PREFIX) {
+                    let src_uuid = article_map.insert_article(&src);
+                    if let Some(cap) = wiki_link_re.captures(&content) {
+                        let dst_uuid = article_map.insert_article(&cap[1]);
+                        article_map.insert_redirect(src_uuid, dst_uuid);
+                    }
                     ArchiveReadState::Ignore
                 } else {
                     ArchiveReadState::Text
@@ -215,19 +480,102 @@ async fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
 
         buf.clear();
 
-        if article_map.uuids.len() - last_article_map_len >= 1000 {
+        if report_progress && article_map.uuids.len() - last_article_map_len >= 1000 {
             last_article_map_len = article_map.uuids.len();
             print!("\rreading archive: {}", last_article_map_len);
             stdout().flush()?;
         }
     }
 
-    println!("\rreading archive: done");
+    if report_progress {
+        println!("\rreading archive: done");
+    }
+
+    Ok(article_map)
+}
+
+async fn read_archive(f: File, metrics: Arc<Metrics>) -> Result<ArticleMap, Box<dyn StdError>> {
+    let f = CountingReader { inner: f, metrics: metrics.clone() };
+    let f = BufReader::new(f);
+    let decompressor = BufReader::new(BzDecoder::new(f));
+    parse_pages(decompressor, &metrics, true)
+}
+
+/// Reads a bzip2-compressed Wikipedia multistream index file, whose lines are
+/// `offset:page_id:title`, and returns its distinct offsets in ascending order.
+/// Only the offset is needed: it marks the start of a self-contained bzip2 stream
+/// holding ~100 pages.
+fn parse_multistream_offsets(index_path: &OsStr) -> Result<Vec<u64>, Box<dyn StdError>> {
+    let f = BufReader::new(BzDecoder::new(BufReader::new(File::open(index_path)?)));
+    let mut offsets = HashSet::new();
+
+    for line in f.lines() {
+        let line = line?;
+        if let Some(offset_str) = line.split(':').next() {
+            offsets.insert(offset_str.parse::<u64>()?);
+        }
+    }
+
+    let mut offsets: Vec<u64> = offsets.into_iter().collect();
+    offsets.sort_unstable();
+    Ok(offsets)
+}
+
+/// Decodes one multistream block starting at `offset`: each bzip2 stream is
+/// self-terminating, so the decoder naturally stops at the next block boundary. The
+/// bare `<page>` elements in a block aren't wrapped in a root element, so a synthetic
+/// `<mediawiki>` root is spliced around them for `quick_xml`.
+fn parse_multistream_block(data_path: &Path, offset: u64, metrics: Arc<Metrics>) -> Result<ArticleMap, Box<dyn StdError>> {
+    let mut f = File::open(data_path)?;
+    f.seek(SeekFrom::Start(offset))?;
+    let f = CountingReader { inner: f, metrics: metrics.clone() };
+    let decompressor = BzDecoder::new(BufReader::new(f));
+
+    let wrapped = io::Cursor::new(&b"<mediawiki>"[..])
+        .chain(decompressor)
+        .chain(io::Cursor::new(&b"</mediawiki>"[..]));
+    parse_pages(BufReader::new(wrapped), &metrics, false)
+}
+
+/// Reads a Wikipedia "multistream" archive: the `.xml.bz2` file is a concatenation of
+/// independent bzip2 streams, each holding ~100 pages, and the accompanying index file
+/// gives the byte offset of each stream. Each block is decoded on its own task on
+/// tokio's blocking thread pool (`spawn_blocking`), and the
+/// partial `ArticleMap`s are merged afterwards with a plain set union — safe because
+/// `common::article_uuid` is a deterministic hash, so identical titles map to identical
+/// UUIDs across workers with no coordination.
+async fn read_archive_multistream(data_path: &OsStr, index_path: &OsStr, metrics: Arc<Metrics>) -> Result<ArticleMap, Box<dyn StdError>> {
+    let offsets = parse_multistream_offsets(index_path)?;
+    println!("reading archive: {} multistream blocks", offsets.len());
+
+    let data_path = data_path.to_os_string();
+    let workers: Vec<JoinHandle<Result<ArticleMap, Box<dyn StdError + Send + Sync>>>> = offsets
+        .into_iter()
+        .map(|offset| {
+            let data_path = data_path.clone();
+            let metrics = metrics.clone();
+            tokio::task::spawn_blocking(move || {
+                parse_multistream_block(Path::new(&data_path), offset, metrics)
+                    .map_err(|err| -> Box<dyn StdError + Send + Sync> { err.to_string().into() })
+            })
+        })
+        .collect();
+
+    let mut article_map = ArticleMap::default();
+    for worker in workers {
+        article_map.merge(worker.await??);
+    }
 
+    println!("reading archive: done");
     Ok(article_map)
 }
 
-async fn load_article_map(input_filepath: &str, dump_filepath: &str) -> Result<ArticleMap, Box<dyn StdError>> {
+async fn load_article_map(
+    input_filepath: &str,
+    dump_filepath: &str,
+    index_path: Option<&OsStr>,
+    metrics: Arc<Metrics>,
+) -> Result<ArticleMap, Box<dyn StdError>> {
     if Path::new(dump_filepath).exists() {
         print!("reading dump...");
         stdout().flush()?;
@@ -235,17 +583,20 @@ async fn load_article_map(input_filepath: &str, dump_filepath: &str) -> Result<A
         println!("\rreading dump: done");
         Ok(article_map)
     } else {
-        let article_map = read_archive(File::open(input_filepath)?).await?;
+        let article_map = match index_path {
+            Some(index_path) => read_archive_multistream(OsStr::new(input_filepath), index_path, metrics).await?,
+            None => read_archive(File::open(input_filepath)?, metrics).await?,
+        };
         bincode::serialize_into(File::create(dump_filepath)?, &article_map)?;
         Ok(article_map)
     }
 }
 
-async fn insert_articles(article_map: &ArticleMap) -> Result<(), proto::ClientError> {
+async fn insert_articles(article_map: &ArticleMap, metrics: Arc<Metrics>) -> Result<(), Box<dyn StdError>> {
     let mut progress = ProgressBar::new(article_map.uuids.len() as u64);
     progress.message("indexing articles: ");
 
-    let mut inserter = BulkInserter::default();
+    let mut inserter = BulkInserter::new(metrics);
     let article_type = indradb::Type::new("article").unwrap();
 
     for (article_name, article_uuid) in &article_map.uuids {
@@ -254,17 +605,17 @@ async fn insert_articles(article_map: &ArticleMap) -> Result<(), proto::ClientEr
         progress.inc();
     }
 
-    inserter.flush().await;
+    inserter.flush().await?;
     progress.finish();
     println!();
     Ok(())
 }
 
-async fn insert_links(article_map: &ArticleMap) -> Result<(), proto::ClientError> {
+async fn insert_links(article_map: &ArticleMap, metrics: Arc<Metrics>) -> Result<(), Box<dyn StdError>> {
     let mut progress = ProgressBar::new(article_map.uuids.len() as u64);
     progress.message("indexing links: ");
 
-    let mut inserter = BulkInserter::default();
+    let mut inserter = BulkInserter::new(metrics);
     let link_type = indradb::Type::new("link").unwrap();
 
     for (src_uuid, dst_uuids) in &article_map.links {
@@ -274,7 +625,25 @@ async fn insert_links(article_map: &ArticleMap) -> Result<(), proto::ClientError
         progress.inc();
     }
 
-    inserter.flush().await;
+    inserter.flush().await?;
+    progress.finish();
+    println!();
+    Ok(())
+}
+
+async fn insert_redirects(article_map: &ArticleMap, metrics: Arc<Metrics>) -> Result<(), Box<dyn StdError>> {
+    let mut progress = ProgressBar::new(article_map.redirects.len() as u64);
+    progress.message("indexing redirects: ");
+
+    let mut inserter = BulkInserter::new(metrics);
+    let redirect_type = indradb::Type::new("redirect").unwrap();
+
+    for (src_uuid, dst_uuid) in &article_map.redirects {
+        inserter.push(indradb::BulkInsertItem::Edge(indradb::EdgeKey::new(*src_uuid, redirect_type.clone(), *dst_uuid))).await;
+        progress.inc();
+    }
+
+    inserter.flush().await?;
     progress.finish();
     println!();
     Ok(())
@@ -292,20 +661,76 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
             .help("Sets the path of the archive cache dump")
             .required(true)
             .index(2))
+        .arg(Arg::with_name("INDEX_PATH")
+            .help("Path to the wikipedia multistream index (enables parallel indexing)")
+            .long("index-path")
+            .value_name("INDEX_PATH")
+            .takes_value(true))
         .arg(Arg::with_name("DATABASE_PATH")
-            .help("Sets the path of the rocksdb results")
+            .help("Sets the path of the results: a rocksdb directory path, or a sled:// URL when --backend=sled")
             .required(true)
             .index(3))
+        .arg(Arg::with_name("BACKEND")
+            .help("Storage backend to run IndraDB against")
+            .long("backend")
+            .value_name("BACKEND")
+            .possible_values(&["rocksdb", "sled"])
+            .default_value("rocksdb"))
+        .arg(Arg::with_name("METRICS_ADDR")
+            .help("Address to serve Prometheus ingestion metrics on, e.g. 127.0.0.1:9090")
+            .long("metrics-addr")
+            .value_name("METRICS_ADDR")
+            .takes_value(true))
+        .arg(Arg::with_name("SEARCH_QUERY")
+            .help("Looks up SEARCH_QUERY in the FST search index after indexing, via prefix and fuzzy (edit distance 2) matching")
+            .long("search-query")
+            .value_name("SEARCH_QUERY")
+            .takes_value(true))
         .get_matches();
 
-    let _server = common::Server::start(matches.value_of("DATABASE_PATH").unwrap())?;
+    let database_path = matches.value_of("DATABASE_PATH").unwrap().to_string();
+    let backend = match matches.value_of("BACKEND").unwrap() {
+        "sled" => common::Backend::Sled { url: database_path },
+        _ => common::Backend::Rocksdb { path: database_path },
+    };
+    let _server = common::Server::start(backend)?;
+
+    let metrics = Arc::new(Metrics::default());
+    if let Some(metrics_addr) = matches.value_of("METRICS_ADDR") {
+        let addr: SocketAddr = metrics_addr.parse()?;
+        tokio::spawn(serve_metrics(addr, metrics.clone()));
+    }
 
-    let article_map = load_article_map(
+    let mut article_map = load_article_map(
         matches.value_of("ARCHIVE_INPUT").unwrap(),
         matches.value_of("ARCHIVE_DUMP").unwrap(),
+        matches.value_of_os("INDEX_PATH"),
+        metrics.clone(),
     ).await?;
+    article_map.resolve_redirects();
+
+    insert_articles(&article_map, metrics.clone()).await?;
+    insert_links(&article_map, metrics.clone()).await?;
+    insert_redirects(&article_map, metrics).await?;
+
+    print!("building search index...");
+    stdout().flush()?;
+    let search_index = build_search_index(&article_map)?;
+    let search_index_path = search_index_path(matches.value_of("ARCHIVE_DUMP").unwrap());
+    bincode::serialize_into(File::create(&search_index_path)?, &search_index)?;
+    println!("\rbuilding search index: done");
+
+    if let Some(query) = matches.value_of("SEARCH_QUERY") {
+        println!("prefix matches for {:?}:", query);
+        for (name, uuid) in search_index.search_prefix(query, 10) {
+            println!("  {} ({})", name, uuid);
+        }
+
+        println!("fuzzy matches for {:?}:", query);
+        for (name, uuid) in search_index.search_fuzzy(query, 2, 10)? {
+            println!("  {} ({})", name, uuid);
+        }
+    }
 
-    insert_articles(&article_map).await.map_err(|err| err.compat())?;
-    insert_links(&article_map).await.map_err(|err| err.compat())?;
     Ok(())
 }
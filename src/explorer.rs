@@ -1,12 +1,94 @@
 use std::convert::Infallible;
 use std::error::Error as StdError;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use indradb::QueryExt;
 use indradb_proto as proto;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use tera::{Context as TeraContext, Tera};
 use warp::{http, reject, reply, Filter};
 
+use crate::search::BkTree;
+
+/// Upper bounds (in seconds) of the `handle_article` latency histogram buckets,
+/// matching the Prometheus text exposition format's cumulative `le` convention.
+const LATENCY_BUCKETS: [f64; 8] = [0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0];
+
+#[derive(Default)]
+struct Metrics {
+    article_lookups_total: AtomicU64,
+    article_not_found_total: AtomicU64,
+    latency_bucket_counts: [AtomicU64; LATENCY_BUCKETS.len()],
+    latency_sum: Mutex<f64>,
+    latency_count: AtomicU64,
+}
+
+impl Metrics {
+    fn observe_latency(&self, seconds: f64) {
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&self.latency_bucket_counts) {
+            if seconds <= *bound {
+                count.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        *self.latency_sum.lock().unwrap() += seconds;
+        self.latency_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn render(&self, article_count: u64, link_count: u64) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP wikipedia_explorer_articles_total Total indexed articles.\n");
+        out.push_str("# TYPE wikipedia_explorer_articles_total gauge\n");
+        out.push_str(&format!("wikipedia_explorer_articles_total {}\n", article_count));
+
+        out.push_str("# HELP wikipedia_explorer_links_total Total indexed links.\n");
+        out.push_str("# TYPE wikipedia_explorer_links_total gauge\n");
+        out.push_str(&format!("wikipedia_explorer_links_total {}\n", link_count));
+
+        out.push_str("# HELP wikipedia_explorer_article_lookups_total Article lookups served.\n");
+        out.push_str("# TYPE wikipedia_explorer_article_lookups_total counter\n");
+        out.push_str(&format!(
+            "wikipedia_explorer_article_lookups_total {}\n",
+            self.article_lookups_total.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# HELP wikipedia_explorer_article_not_found_total Article lookups that returned 404.\n");
+        out.push_str("# TYPE wikipedia_explorer_article_not_found_total counter\n");
+        out.push_str(&format!(
+            "wikipedia_explorer_article_not_found_total {}\n",
+            self.article_not_found_total.load(Ordering::Relaxed)
+        ));
+
+        out.push_str("# HELP wikipedia_explorer_article_latency_seconds handle_article request latency.\n");
+        out.push_str("# TYPE wikipedia_explorer_article_latency_seconds histogram\n");
+        // `observe_latency` already increments every bucket whose bound is >= the
+        // observed value, so each count here is already cumulative — printed as-is,
+        // not summed again.
+        for (bound, count) in LATENCY_BUCKETS.iter().zip(&self.latency_bucket_counts) {
+            out.push_str(&format!(
+                "wikipedia_explorer_article_latency_seconds_bucket{{le=\"{}\"}} {}\n",
+                bound, count.load(Ordering::Relaxed)
+            ));
+        }
+        out.push_str(&format!(
+            "wikipedia_explorer_article_latency_seconds_bucket{{le=\"+Inf\"}} {}\n",
+            self.latency_count.load(Ordering::Relaxed)
+        ));
+        out.push_str(&format!(
+            "wikipedia_explorer_article_latency_seconds_sum {}\n",
+            *self.latency_sum.lock().unwrap()
+        ));
+        out.push_str(&format!(
+            "wikipedia_explorer_article_latency_seconds_count {}\n",
+            self.latency_count.load(Ordering::Relaxed)
+        ));
+
+        out
+    }
+}
+
 const INDEX: &str = r#"
 <form method="get" action="/article">
     <input name="name" value="" type="text" />
@@ -48,6 +130,14 @@ const ARTICLE_TEMPLATE: &str = r#"
 {% endif %}
 "#;
 
+const SEARCH_TEMPLATE: &str = r#"
+<ul>
+{% for name in results %}
+    <li><a href="/article?name={{ name | urlencode }}">{{ name }}</a></li>
+{% endfor %}
+</ul>
+"#;
+
 #[derive(Debug)]
 enum Error {
     Client { err: proto::ClientError },
@@ -87,17 +177,37 @@ struct ArticleQueryParams {
     name: String,
 }
 
+#[derive(Deserialize)]
+struct SearchQueryParams {
+    q: String,
+}
+
+const SEARCH_EDIT_DISTANCE_TOLERANCE: u32 = 2;
+const SEARCH_RESULT_LIMIT: usize = 10;
+
+#[derive(Serialize)]
+struct ArticleJson {
+    id: String,
+    name: String,
+    properties: Vec<(String, String)>,
+    linked_articles: Vec<(String, String)>,
+}
+
+#[derive(Serialize)]
+struct ErrorJson {
+    error: String,
+}
+
 async fn handle_index() -> Result<impl warp::Reply, Infallible> {
     Ok(reply::html(INDEX))
 }
 
-async fn handle_article(
-    mut client: proto::Client,
-    tera: Tera,
-    query: ArticleQueryParams,
-) -> Result<impl warp::Reply, warp::Rejection> {
+async fn fetch_article(
+    client: &mut proto::Client,
+    name: &str,
+) -> Result<(uuid::Uuid, Vec<(String, String)>, Vec<(String, String)>), warp::Rejection> {
     let name_identifier = indradb::Identifier::new("name").unwrap();
-    let property_value = indradb::Json::new(serde_json::Value::String(query.name.clone()));
+    let property_value = indradb::Json::new(serde_json::Value::String(name.to_string()));
     let base_q = indradb::VertexWithPropertyValueQuery::new(name_identifier, property_value);
 
     let results = map_result(client.get(base_q.clone().include().properties().unwrap()).await)?;
@@ -105,9 +215,7 @@ async fn handle_article(
 
     let article_id = if let indradb::QueryOutputValue::Vertices(article_vertices) = &results[0] {
         if article_vertices.is_empty() {
-            return Err(reject::custom(Error::ArticleNotFound {
-                name: query.name.clone(),
-            }));
+            return Err(reject::custom(Error::ArticleNotFound { name: name.to_string() }));
         }
         assert_eq!(article_vertices.len(), 1);
         article_vertices[0].id
@@ -152,6 +260,26 @@ async fn handle_article(
         linked_articles
     };
 
+    Ok((article_id, article_properties, linked_articles))
+}
+
+async fn handle_article(
+    mut client: proto::Client,
+    tera: Tera,
+    metrics: Arc<Metrics>,
+    query: ArticleQueryParams,
+) -> Result<impl warp::Reply, warp::Rejection> {
+    let start = Instant::now();
+    let result = fetch_article(&mut client, &query.name).await;
+    metrics.observe_latency(start.elapsed().as_secs_f64());
+    metrics.article_lookups_total.fetch_add(1, Ordering::Relaxed);
+    if let Err(rejection) = &result {
+        if matches!(rejection.find::<Error>(), Some(Error::ArticleNotFound { .. })) {
+            metrics.article_not_found_total.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    let (article_id, article_properties, linked_articles) = result?;
+
     let mut context = TeraContext::new();
     context.insert("article_name", &query.name);
     context.insert("article_id", &article_id.to_string());
@@ -161,6 +289,65 @@ async fn handle_article(
     Ok(reply::html(rendered))
 }
 
+async fn handle_article_api(
+    mut client: proto::Client,
+    query: ArticleQueryParams,
+) -> Result<Box<dyn warp::Reply>, Infallible> {
+    match fetch_article(&mut client, &query.name).await {
+        Ok((article_id, properties, linked_articles)) => {
+            let body = ArticleJson {
+                id: article_id.to_string(),
+                name: query.name,
+                properties,
+                linked_articles,
+            };
+            Ok(Box::new(reply::json(&body)))
+        }
+        Err(rejection) => {
+            if let Some(Error::ArticleNotFound { name }) = rejection.find::<Error>() {
+                let body = ErrorJson {
+                    error: format!("article not found: {}", name),
+                };
+                Ok(Box::new(reply::with_status(reply::json(&body), http::StatusCode::NOT_FOUND)))
+            } else {
+                let body = ErrorJson {
+                    error: "internal error".to_string(),
+                };
+                Ok(Box::new(reply::with_status(
+                    reply::json(&body),
+                    http::StatusCode::INTERNAL_SERVER_ERROR,
+                )))
+            }
+        }
+    }
+}
+
+async fn handle_search(
+    search_index: Arc<Option<BkTree>>,
+    tera: Tera,
+    query: SearchQueryParams,
+) -> Result<impl warp::Reply, Infallible> {
+    let results = match &*search_index {
+        Some(tree) => tree.search(&query.q, SEARCH_EDIT_DISTANCE_TOLERANCE, SEARCH_RESULT_LIMIT),
+        None => Vec::new(),
+    };
+
+    let mut context = TeraContext::new();
+    context.insert("results", &results);
+    let rendered = tera.render("search.html", &context).unwrap();
+    Ok(reply::html(rendered))
+}
+
+async fn handle_metrics(graph_counts: (u64, u64), metrics: Arc<Metrics>) -> Result<impl warp::Reply, Infallible> {
+    let (article_count, link_count) = graph_counts;
+
+    Ok(reply::with_header(
+        metrics.render(article_count, link_count),
+        "content-type",
+        "text/plain; version=0.0.4",
+    ))
+}
+
 fn with_client(client: proto::Client) -> impl Filter<Extract = (proto::Client,), Error = Infallible> + Clone {
     warp::any().map(move || client.clone())
 }
@@ -169,9 +356,30 @@ fn with_templating(tera: Tera) -> impl Filter<Extract = (Tera,), Error = Infalli
     warp::any().map(move || tera.clone())
 }
 
-pub async fn run(client: proto::Client, port: u16) -> Result<(), Box<dyn StdError>> {
+fn with_search_index(
+    search_index: Arc<Option<BkTree>>,
+) -> impl Filter<Extract = (Arc<Option<BkTree>>,), Error = Infallible> + Clone {
+    warp::any().map(move || search_index.clone())
+}
+
+fn with_metrics(metrics: Arc<Metrics>) -> impl Filter<Extract = (Arc<Metrics>,), Error = Infallible> + Clone {
+    warp::any().map(move || metrics.clone())
+}
+
+fn with_graph_counts(graph_counts: (u64, u64)) -> impl Filter<Extract = ((u64, u64),), Error = Infallible> + Clone {
+    warp::any().map(move || graph_counts)
+}
+
+pub async fn run(
+    client: proto::Client,
+    port: u16,
+    search_index: Option<BkTree>,
+    graph_counts: (u64, u64),
+) -> Result<(), Box<dyn StdError>> {
     let mut tera = Tera::default();
-    tera.add_raw_templates(vec![("article.html", ARTICLE_TEMPLATE)])?;
+    tera.add_raw_templates(vec![("article.html", ARTICLE_TEMPLATE), ("search.html", SEARCH_TEMPLATE)])?;
+    let search_index = Arc::new(search_index);
+    let metrics = Arc::new(Metrics::default());
 
     let index_route = warp::path::end().and(warp::get()).and_then(handle_index);
 
@@ -179,10 +387,35 @@ pub async fn run(client: proto::Client, port: u16) -> Result<(), Box<dyn StdErro
         .and(warp::get())
         .and(with_client(client.clone()))
         .and(with_templating(tera.clone()))
+        .and(with_metrics(metrics.clone()))
         .and(warp::query::<ArticleQueryParams>())
         .and_then(handle_article);
 
-    let routes = index_route.or(article_route).recover(handle_rejection);
+    let article_api_route = warp::path!("api" / "article")
+        .and(warp::get())
+        .and(with_client(client.clone()))
+        .and(warp::query::<ArticleQueryParams>())
+        .and_then(handle_article_api);
+
+    let search_route = warp::path("search")
+        .and(warp::get())
+        .and(with_search_index(search_index))
+        .and(with_templating(tera.clone()))
+        .and(warp::query::<SearchQueryParams>())
+        .and_then(handle_search);
+
+    let metrics_route = warp::path("metrics")
+        .and(warp::get())
+        .and(with_graph_counts(graph_counts))
+        .and(with_metrics(metrics))
+        .and_then(handle_metrics);
+
+    let routes = index_route
+        .or(article_route)
+        .or(article_api_route)
+        .or(search_route)
+        .or(metrics_route)
+        .recover(handle_rejection);
 
     warp::serve(routes).run(([127, 0, 0, 1], port)).await;
 
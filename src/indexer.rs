@@ -2,12 +2,15 @@ use std::collections::{HashMap, HashSet};
 use std::error::Error as StdError;
 use std::ffi::OsStr;
 use std::fs::File;
-use std::io::{stdout, BufReader, Write};
+use std::io::{stdout, BufRead, BufReader, Cursor, Read, Seek, SeekFrom, Write};
 use std::mem::replace;
+use std::path::Path;
 use std::str;
 use std::time::Instant;
 
+use blake2b_simd::Params;
 use bzip2::bufread::BzDecoder;
+use flate2::bufread::GzDecoder;
 use indradb_proto as proto;
 use pbr::ProgressBar;
 use quick_xml::{events::Event, Reader};
@@ -15,6 +18,10 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tokio::task::JoinHandle;
 use uuid::Uuid;
+use xz2::bufread::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
+
+use crate::search::BkTree;
 
 const REQUEST_BUFFER_SIZE: usize = 10_000;
 
@@ -22,10 +29,26 @@ const ARTICLE_NAME_PREFIX_BLACKLIST: [&str; 7] = ["Wikipedia:", "WP:", ":", "Fil
 
 const REDIRECT_PREFIX: &str = "#REDIRECT [[";
 
+lazy_static! {
+    static ref HASHER_PARAMS: Params = {
+        let mut params = Params::new();
+        params.hash_length(16);
+        params
+    };
+}
+
+// Deterministic so that partial `ArticleMap`s built by independent multistream
+// workers can be merged with a plain set union, with no cross-worker coordination.
+fn article_uuid<T: AsRef<[u8]>>(name: T) -> Uuid {
+    let hash = HASHER_PARAMS.hash(name.as_ref());
+    Uuid::from_slice(hash.as_bytes()).unwrap()
+}
+
 #[derive(Default, Serialize, Deserialize)]
 pub struct ArticleMap {
     pub uuids: HashMap<String, Uuid>,
     pub links: HashMap<Uuid, HashSet<Uuid>>,
+    pub redirects: HashMap<Uuid, Uuid>,
 }
 
 impl ArticleMap {
@@ -33,7 +56,7 @@ impl ArticleMap {
         if let Some(&uuid) = self.uuids.get(name) {
             return uuid;
         }
-        let uuid = indradb::util::generate_uuid_v1();
+        let uuid = article_uuid(name);
         self.uuids.insert(name.to_string(), uuid);
         uuid
     }
@@ -43,6 +66,10 @@ impl ArticleMap {
         container.insert(dst_uuid);
     }
 
+    pub fn insert_redirect(&mut self, src_uuid: Uuid, dst_uuid: Uuid) {
+        self.redirects.insert(src_uuid, dst_uuid);
+    }
+
     pub fn article_len(&self) -> u64 {
         self.uuids.len() as u64
     }
@@ -50,6 +77,24 @@ impl ArticleMap {
     pub fn link_len(&self) -> u64 {
         self.links.values().map(|v| v.len()).sum::<usize>() as u64
     }
+
+    /// Unions another partial map into this one. Safe because `article_uuid` is a
+    /// pure function of the article name, so identical titles hash identically
+    /// regardless of which worker parsed them.
+    fn merge(&mut self, other: ArticleMap) {
+        self.uuids.extend(other.uuids);
+        for (src_uuid, dst_uuids) in other.links {
+            self.links.entry(src_uuid).or_insert_with(HashSet::default).extend(dst_uuids);
+        }
+        self.redirects.extend(other.redirects);
+    }
+
+    /// Rewrites every link destination through the redirect map (following chains) so
+    /// edges land on canonical articles instead of dangling on a redirect stub.
+    /// Delegates to `common::resolve_redirects`, shared with `crawler::ArticleMap`.
+    pub fn resolve_redirects(&mut self) {
+        common::resolve_redirects(&mut self.links, &self.redirects);
+    }
 }
 
 enum ArchiveReadState {
@@ -60,12 +105,12 @@ enum ArchiveReadState {
     Text,
 }
 
-fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
+/// Parses an already-decompressed range of `<page>` elements into an `ArticleMap`. Used
+/// both for a whole single-stream archive and for one worker's slice of a multistream one.
+fn parse_pages<R: BufRead>(decompressor: R, report_progress: bool) -> Result<ArticleMap, Box<dyn StdError>> {
     let mut article_map = ArticleMap::default();
 
     let mut buf = Vec::new();
-    let f = BufReader::new(f);
-    let decompressor = BufReader::new(BzDecoder::new(f));
     let mut reader = Reader::from_reader(decompressor);
     reader.trim_text(true);
     reader.check_end_names(false);
@@ -85,8 +130,10 @@ fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
 
     let wiki_link_re = Regex::new(r"\[\[([^\[\]|]+)(|[\]]+)?\]\]").unwrap();
 
-    print!("reading archive");
-    stdout().flush()?;
+    if report_progress {
+        print!("reading archive");
+        stdout().flush()?;
+    }
 
     loop {
         state = match (state, reader.read_event(&mut buf)?) {
@@ -116,19 +163,21 @@ fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
 
                 total_read_count += 1;
 
-                let elapsed = progress_start.elapsed();
-                if elapsed.as_secs() >= 1 {
-                    let read_speed_str = (total_read_count - last_total_read_count).to_string();
-                    print!(
-                        "\rreading archive: {} articles ({}/s)",
-                        total_read_count, read_speed_str
-                    );
-                    for _ in 0..(10i16 - read_speed_str.len() as i16) {
-                        print!(" ");
+                if report_progress {
+                    let elapsed = progress_start.elapsed();
+                    if elapsed.as_secs() >= 1 {
+                        let read_speed_str = (total_read_count - last_total_read_count).to_string();
+                        print!(
+                            "\rreading archive: {} articles ({}/s)",
+                            total_read_count, read_speed_str
+                        );
+                        for _ in 0..(10i16 - read_speed_str.len() as i16) {
+                            print!(" ");
+                        }
+                        stdout().flush()?;
+                        progress_start = Instant::now();
+                        last_total_read_count = total_read_count;
                     }
-                    stdout().flush()?;
-                    progress_start = Instant::now();
-                    last_total_read_count = total_read_count;
                 }
 
                 ArchiveReadState::Ignore
@@ -152,9 +201,12 @@ fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
                 debug_assert!(content.is_empty());
                 content.push_str(str::from_utf8(e)?);
 
-                let blacklisted = content.starts_with(REDIRECT_PREFIX);
-
-                if blacklisted {
+                if content.starts_with(REDIRECT_PREFIX) {
+                    let src_uuid = article_map.insert_article(&src);
+                    if let Some(cap) = wiki_link_re.captures(&content) {
+                        let dst_uuid = article_map.insert_article(&cap[1]);
+                        article_map.insert_redirect(src_uuid, dst_uuid);
+                    }
                     ArchiveReadState::Ignore
                 } else {
                     ArchiveReadState::Text
@@ -168,7 +220,128 @@ fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
         buf.clear();
     }
 
-    println!();
+    if report_progress {
+        println!();
+    }
+    Ok(article_map)
+}
+
+/// Detects the compression codec of `path` — first by file extension, then by
+/// sniffing the leading magic bytes — and wraps `f` in the matching decoder. Falls
+/// back to treating the contents as plain, uncompressed XML.
+fn wrap_decompressor(path: &OsStr, f: File) -> Result<Box<dyn BufRead>, Box<dyn StdError>> {
+    let mut f = BufReader::new(f);
+    let path = path.to_string_lossy();
+
+    let by_extension = if path.ends_with(".bz2") {
+        Some("bz2")
+    } else if path.ends_with(".gz") {
+        Some("gz")
+    } else if path.ends_with(".zst") {
+        Some("zst")
+    } else if path.ends_with(".xz") {
+        Some("xz")
+    } else {
+        None
+    };
+
+    let codec = match by_extension {
+        Some(codec) => codec,
+        None => {
+            let magic = f.fill_buf()?;
+            if magic.starts_with(b"BZh") {
+                "bz2"
+            } else if magic.starts_with(&[0x1f, 0x8b]) {
+                "gz"
+            } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+                "zst"
+            } else if magic.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]) {
+                "xz"
+            } else {
+                "xml"
+            }
+        }
+    };
+
+    Ok(match codec {
+        "bz2" => Box::new(BufReader::new(BzDecoder::new(f))),
+        "gz" => Box::new(BufReader::new(GzDecoder::new(f))),
+        "zst" => Box::new(BufReader::new(ZstdDecoder::new(f)?)),
+        "xz" => Box::new(BufReader::new(XzDecoder::new(f))),
+        _ => Box::new(f),
+    })
+}
+
+fn read_archive(archive_path: &OsStr) -> Result<ArticleMap, Box<dyn StdError>> {
+    let decompressor = wrap_decompressor(archive_path, File::open(archive_path)?)?;
+    parse_pages(decompressor, true)
+}
+
+/// Reads a bzip2-compressed Wikipedia multistream index file, whose lines are
+/// `offset:page_id:title`, and returns its distinct offsets in ascending order.
+/// Only the offset is needed: it marks the start of a self-contained bzip2 stream
+/// holding ~100 pages.
+fn parse_multistream_offsets(index_path: &OsStr) -> Result<Vec<u64>, Box<dyn StdError>> {
+    let f = BufReader::new(BzDecoder::new(BufReader::new(File::open(index_path)?)));
+    let mut offsets = HashSet::new();
+
+    for line in f.lines() {
+        let line = line?;
+        if let Some(offset_str) = line.split(':').next() {
+            offsets.insert(offset_str.parse::<u64>()?);
+        }
+    }
+
+    let mut offsets: Vec<u64> = offsets.into_iter().collect();
+    offsets.sort_unstable();
+    Ok(offsets)
+}
+
+/// Decodes one multistream block starting at `offset`: each bzip2 stream is
+/// self-terminating, so the decoder naturally stops at the next block boundary.
+/// The bare `<page>` elements in a block aren't wrapped in a root element, so a
+/// synthetic `<mediawiki>` root is spliced around them for `quick_xml`.
+fn parse_multistream_block(data_path: &Path, offset: u64) -> Result<ArticleMap, Box<dyn StdError>> {
+    let mut f = File::open(data_path)?;
+    f.seek(SeekFrom::Start(offset))?;
+    let decompressor = wrap_decompressor(data_path.as_os_str(), f)?;
+
+    let wrapped = Cursor::new(&b"<mediawiki>"[..])
+        .chain(decompressor)
+        .chain(Cursor::new(&b"</mediawiki>"[..]));
+    parse_pages(BufReader::new(wrapped), false)
+}
+
+/// Reads a Wikipedia "multistream" archive: the `.xml.bz2` file is a concatenation of
+/// independent bzip2 streams, each holding ~100 pages, and the accompanying index file
+/// gives the byte offset of each stream. Each block is decoded on its own blocking
+/// worker task, running the same state machine as the single-stream path over just
+/// that block, and the partial `ArticleMap`s are merged with a set union afterwards.
+pub async fn read_archive_multistream(data_path: &OsStr, index_path: &OsStr) -> Result<ArticleMap, Box<dyn StdError>> {
+    let offsets = parse_multistream_offsets(index_path)?;
+    println!("reading archive: {} multistream blocks", offsets.len());
+
+    let data_path = data_path.to_os_string();
+    let workers: Vec<JoinHandle<Result<ArticleMap, Box<dyn StdError + Send + Sync>>>> = offsets
+        .into_iter()
+        .map(|offset| {
+            let data_path = data_path.clone();
+            tokio::task::spawn_blocking(move || {
+                parse_multistream_block(Path::new(&data_path), offset).map_err(|err| err.to_string().into())
+            })
+        })
+        .collect();
+
+    let mut article_map = ArticleMap::default();
+    for worker in workers {
+        article_map.merge(worker.await??);
+    }
+
+    println!(
+        "reading archive: done ({} articles, {} links)",
+        article_map.article_len(),
+        article_map.link_len()
+    );
     Ok(article_map)
 }
 
@@ -273,12 +446,161 @@ async fn insert_links(client: proto::Client, article_map: &ArticleMap) -> Result
     Ok(())
 }
 
-pub async fn run(mut client: proto::Client, archive_path: &OsStr) -> Result<(), Box<dyn StdError>> {
+/// Inserts a distinct `redirect` edge for each redirect page, so callers can tell a
+/// true link from a redirect rather than only ever seeing the already-resolved one.
+async fn insert_redirects(client: proto::Client, article_map: &ArticleMap) -> Result<(), proto::ClientError> {
+    let mut progress = ProgressBar::new(article_map.redirects.len() as u64);
+    progress.message("indexing redirects: ");
+
+    let mut inserter = BulkInserter::new(client);
+    let redirect_type = indradb::Identifier::new("redirect").unwrap();
+
+    for (src_uuid, dst_uuid) in &article_map.redirects {
+        inserter
+            .push(indradb::BulkInsertItem::Edge(indradb::Edge::new(
+                *src_uuid,
+                redirect_type,
+                *dst_uuid,
+            )))
+            .await;
+        progress.inc();
+    }
+
+    inserter.flush().await;
+    progress.finish();
+    println!();
+    Ok(())
+}
+
+/// Derives the on-disk path for the BK-tree search index from the archive path, so
+/// `explore` can find it without an extra flag.
+pub fn search_index_path(archive_path: &OsStr) -> std::path::PathBuf {
+    let mut path = Path::new(archive_path).as_os_str().to_os_string();
+    path.push(".bktree");
+    std::path::PathBuf::from(path)
+}
+
+fn build_and_save_search_index(archive_path: &OsStr, article_map: &ArticleMap) -> Result<(), Box<dyn StdError>> {
+    let mut tree = BkTree::default();
+    for name in article_map.uuids.keys() {
+        tree.insert(name.clone());
+    }
+    bincode::serialize_into(File::create(search_index_path(archive_path))?, &tree)?;
+    Ok(())
+}
+
+/// Derives the on-disk path for the `ArticleMap` bincode dump from the archive path,
+/// so `export` can find it without an extra flag.
+pub fn dump_path(archive_path: &OsStr) -> std::path::PathBuf {
+    let mut path = Path::new(archive_path).as_os_str().to_os_string();
+    path.push(".dump");
+    std::path::PathBuf::from(path)
+}
+
+pub fn write_dump(dump_path: &OsStr, article_map: &ArticleMap) -> Result<(), Box<dyn StdError>> {
+    bincode::serialize_into(File::create(dump_path)?, article_map)?;
+    Ok(())
+}
+
+pub fn read_dump(dump_path: &OsStr) -> Result<ArticleMap, Box<dyn StdError>> {
+    Ok(bincode::deserialize_from(File::open(dump_path)?)?)
+}
+
+/// Writes the article/link graph as a CSV node list (`uuid,name`).
+pub fn export_csv_nodes(article_map: &ArticleMap, mut w: impl Write) -> Result<(), Box<dyn StdError>> {
+    for (name, uuid) in &article_map.uuids {
+        writeln!(w, "{},{}", uuid, csv_escape(name))?;
+    }
+    Ok(())
+}
+
+/// Writes the article/link graph as a CSV edge list (`src_name,dst_name`).
+pub fn export_csv_edges(article_map: &ArticleMap, mut w: impl Write) -> Result<(), Box<dyn StdError>> {
+    let names_by_uuid: HashMap<&Uuid, &String> = article_map.uuids.iter().map(|(name, uuid)| (uuid, name)).collect();
+    for (src_uuid, dst_uuids) in &article_map.links {
+        let src_name = match names_by_uuid.get(src_uuid) {
+            Some(name) => *name,
+            None => continue,
+        };
+        for dst_uuid in dst_uuids {
+            if let Some(dst_name) = names_by_uuid.get(dst_uuid) {
+                writeln!(w, "{},{}", csv_escape(src_name), csv_escape(dst_name))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn csv_escape(s: &str) -> String {
+    if s.contains(',') || s.contains('"') {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Writes the article/link graph as a GraphML document, for import into tools like
+/// Gephi or networkx.
+pub fn export_graphml(article_map: &ArticleMap, mut w: impl Write) -> Result<(), Box<dyn StdError>> {
+    writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(w, r#"<graphml xmlns="http://graphml.graphdrawing.org/xmlns">"#)?;
+    writeln!(w, r#"  <key id="name" for="node" attr.name="name" attr.type="string"/>"#)?;
+    writeln!(w, r#"  <graph id="wikipedia" edgedefault="directed">"#)?;
+
+    for (name, uuid) in &article_map.uuids {
+        writeln!(w, r#"    <node id="{}"><data key="name">{}</data></node>"#, uuid, xml_escape(name))?;
+    }
+    for (src_uuid, dst_uuids) in &article_map.links {
+        for dst_uuid in dst_uuids {
+            writeln!(w, r#"    <edge source="{}" target="{}"/>"#, src_uuid, dst_uuid)?;
+        }
+    }
+
+    writeln!(w, "  </graph>")?;
+    writeln!(w, "</graphml>")?;
+    Ok(())
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Loads the `ArticleMap` dump for `archive_path` and writes it out as `nodes.csv`,
+/// `edges.csv`, and `graph.graphml` in `out_dir`, for use with external graph tools.
+pub fn export(archive_path: &OsStr, out_dir: &OsStr) -> Result<(), Box<dyn StdError>> {
+    let article_map = read_dump(&dump_path(archive_path))?;
+    let out_dir = Path::new(out_dir);
+
+    export_csv_nodes(&article_map, File::create(out_dir.join("nodes.csv"))?)?;
+    export_csv_edges(&article_map, File::create(out_dir.join("edges.csv"))?)?;
+    export_graphml(&article_map, File::create(out_dir.join("graph.graphml"))?)?;
+
+    println!("exported to {}", out_dir.display());
+    Ok(())
+}
+
+pub async fn run(
+    mut client: proto::Client,
+    archive_path: &OsStr,
+    index_path: Option<&OsStr>,
+) -> Result<(), Box<dyn StdError>> {
     let start_time = Instant::now();
     client.index_property(indradb::Identifier::new("name")?).await?;
-    let article_map = read_archive(File::open(archive_path)?)?;
+
+    let mut article_map = match index_path {
+        Some(index_path) => read_archive_multistream(archive_path, index_path).await?,
+        None => read_archive(archive_path)?,
+    };
+    article_map.resolve_redirects();
+
     insert_articles(client.clone(), &article_map).await?;
-    insert_links(client, &article_map).await?;
+    insert_links(client.clone(), &article_map).await?;
+    insert_redirects(client, &article_map).await?;
+    build_and_save_search_index(archive_path, &article_map)?;
+    write_dump(&dump_path(archive_path), &article_map)?;
     println!("finished in {} seconds", start_time.elapsed().as_secs());
     Ok(())
 }
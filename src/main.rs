@@ -3,8 +3,10 @@ extern crate clap;
 #[macro_use]
 extern crate lazy_static;
 
+mod analyzer;
 mod explorer;
 mod indexer;
+mod search;
 
 use std::convert::TryInto;
 use std::error::Error as StdError;
@@ -77,6 +79,12 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
         .required(true)
         .takes_value(true);
 
+    let index_path_arg = Arg::with_name("INDEX_PATH")
+        .help("path to the wikipedia multistream index (enables parallel indexing)")
+        .long("index-path")
+        .value_name("INDEX_PATH")
+        .takes_value(true);
+
     let port_arg = Arg::with_name("PORT")
         .help("port to run the webserver on")
         .long("port")
@@ -84,6 +92,24 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
         .default_value("8080")
         .takes_value(true);
 
+    let search_index_arg = Arg::with_name("SEARCH_ARCHIVE_PATH")
+        .help("path to the archive used for indexing, so its BK-tree search index can be loaded")
+        .long("archive-path")
+        .value_name("ARCHIVE_PATH")
+        .takes_value(true);
+
+    let export_archive_arg = Arg::with_name("EXPORT_ARCHIVE_PATH")
+        .help("path to the archive that was indexed, used to locate its ArticleMap dump")
+        .value_name("ARCHIVE_PATH")
+        .required(true)
+        .index(1);
+
+    let export_out_dir_arg = Arg::with_name("OUT_DIR")
+        .help("directory to write nodes.csv, edges.csv, and graph.graphml into")
+        .value_name("OUT_DIR")
+        .required(true)
+        .index(2);
+
     let matches = App::new("IndraDB wikipedia example")
         .about("demonstrates IndraDB with the wikipedia dataset")
         .arg(
@@ -94,20 +120,51 @@ pub async fn main() -> Result<(), Box<dyn StdError>> {
                 .required(true)
                 .takes_value(true),
         )
-        .subcommand(SubCommand::with_name("index").arg(&archive_arg))
-        .subcommand(SubCommand::with_name("explore").arg(&port_arg))
+        .subcommand(SubCommand::with_name("index").arg(&archive_arg).arg(&index_path_arg))
+        .subcommand(SubCommand::with_name("explore").arg(&port_arg).arg(&search_index_arg))
+        .subcommand(SubCommand::with_name("export").arg(&export_archive_arg).arg(&export_out_dir_arg))
+        .subcommand(SubCommand::with_name("analyze").arg(&archive_arg))
         .get_matches();
 
+    if let Some(matches) = matches.subcommand_matches("export") {
+        let archive_path = matches.value_of_os("EXPORT_ARCHIVE_PATH").unwrap();
+        let out_dir = matches.value_of_os("OUT_DIR").unwrap();
+        return indexer::export(archive_path, out_dir);
+    }
+
     let database_path = matches.value_of_os("DATABASE_PATH").unwrap();
     let _server = Server::start(database_path)?;
     let client = get_client_retrying().await.unwrap();
 
     if let Some(matches) = matches.subcommand_matches("index") {
         let archive_path = matches.value_of_os("ARCHIVE_PATH").unwrap();
-        indexer::run(client, archive_path).await
+        let index_path = matches.value_of_os("INDEX_PATH");
+        indexer::run(client, archive_path, index_path).await
     } else if let Some(matches) = matches.subcommand_matches("explore") {
         let port = value_t!(matches.value_of("PORT"), u16).unwrap_or_else(|err| err.exit());
-        explorer::run(client, port).await
+        let (search_index, graph_counts) = match matches.value_of_os("SEARCH_ARCHIVE_PATH") {
+            Some(archive_path) => {
+                let path = indexer::search_index_path(archive_path);
+                let search_index = if path.exists() {
+                    Some(bincode::deserialize_from(std::fs::File::open(path)?)?)
+                } else {
+                    None
+                };
+
+                // Counted once here from the on-disk ArticleMap dump, rather than via a
+                // live query on every /metrics scrape — a dedicated count query isn't
+                // worth round-tripping the whole vertex/edge set for a gauge.
+                let article_map = indexer::read_dump(&indexer::dump_path(archive_path))?;
+                let graph_counts = (article_map.article_len(), article_map.link_len());
+
+                (search_index, graph_counts)
+            }
+            None => (None, (0, 0)),
+        };
+        explorer::run(client, port, search_index, graph_counts).await
+    } else if let Some(matches) = matches.subcommand_matches("analyze") {
+        let archive_path = matches.value_of_os("ARCHIVE_PATH").unwrap();
+        analyzer::run(client, archive_path).await
     } else {
         panic!("no subcommand specified");
     }
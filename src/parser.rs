@@ -1,13 +1,16 @@
 use std::fs::File;
-use std::io::{BufReader, Write, stdout};
+use std::io::{BufReader, BufRead, Write, stdout};
 use std::error::Error as StdError;
 use std::str;
 
 use super::util::ArticleMap;
 
 use bzip2::bufread::BzDecoder;
+use flate2::bufread::GzDecoder;
 use quick_xml::{Reader, events::Event};
 use regex::Regex;
+use xz2::bufread::XzDecoder;
+use zstd::stream::read::Decoder as ZstdDecoder;
 
 const ARTICLE_NAME_PREFIX_BLACKLIST: [&str; 7] = [
     "Wikipedia:",
@@ -21,6 +24,51 @@ const ARTICLE_NAME_PREFIX_BLACKLIST: [&str; 7] = [
 
 const REDIRECT_PREFIX: &str = "#REDIRECT [[";
 
+/// Detects the compression codec of `path` — first by file extension, then by
+/// sniffing the leading magic bytes — and wraps `f` in the matching decoder.
+/// Falls back to treating the contents as plain, uncompressed XML.
+fn wrap_decompressor(path: &str, f: File) -> Result<Box<dyn BufRead>, Box<dyn StdError>> {
+    let mut f = BufReader::new(f);
+
+    let by_extension = if path.ends_with(".bz2") {
+        Some("bz2")
+    } else if path.ends_with(".gz") {
+        Some("gz")
+    } else if path.ends_with(".zst") {
+        Some("zst")
+    } else if path.ends_with(".xz") {
+        Some("xz")
+    } else {
+        None
+    };
+
+    let codec = match by_extension {
+        Some(codec) => codec,
+        None => {
+            let magic = f.fill_buf()?;
+            if magic.starts_with(b"BZh") {
+                "bz2"
+            } else if magic.starts_with(&[0x1f, 0x8b]) {
+                "gz"
+            } else if magic.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+                "zst"
+            } else if magic.starts_with(&[0xfd, 0x37, 0x7a, 0x58, 0x5a, 0x00]) {
+                "xz"
+            } else {
+                "xml"
+            }
+        }
+    };
+
+    Ok(match codec {
+        "bz2" => Box::new(BufReader::new(BzDecoder::new(f))),
+        "gz" => Box::new(BufReader::new(GzDecoder::new(f))),
+        "zst" => Box::new(BufReader::new(ZstdDecoder::new(f)?)),
+        "xz" => Box::new(BufReader::new(XzDecoder::new(f))),
+        _ => Box::new(f),
+    })
+}
+
 enum ArchiveReadState {
     Ignore,
     Page,
@@ -29,12 +77,11 @@ enum ArchiveReadState {
     Text,
 }
 
-fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
+fn read_archive(archive_path: &str) -> Result<ArticleMap, Box<dyn StdError>> {
     let mut article_map = ArticleMap::default();
 
     let mut buf = Vec::new();
-    let f = BufReader::new(f);
-    let decompressor = BufReader::new(BzDecoder::new(f));
+    let decompressor = wrap_decompressor(archive_path, File::open(archive_path)?)?;
     let mut reader = Reader::from_reader(decompressor);
     reader.trim_text(true);
     reader.check_end_names(false);
@@ -135,7 +182,7 @@ fn read_archive(f: File) -> Result<ArticleMap, Box<dyn StdError>> {
 }
 
 pub fn write_dump(archive_path: &str, dump_path: &str) -> Result<(), Box<dyn StdError>> {
-    let article_map = read_archive(File::open(archive_path)?)?;
+    let article_map = read_archive(archive_path)?;
     bincode::serialize_into(File::create(dump_path)?, &article_map)?;
     Ok(())
 }
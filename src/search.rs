@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+/// Computes the Levenshtein edit distance between two strings.
+fn edit_distance(a: &str, b: &str) -> u32 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut prev_row: Vec<u32> = (0..=b.len() as u32).collect();
+    let mut cur_row = vec![0u32; b.len() + 1];
+
+    for (i, &a_ch) in a.iter().enumerate() {
+        cur_row[0] = i as u32 + 1;
+
+        for (j, &b_ch) in b.iter().enumerate() {
+            let cost = if a_ch == b_ch { 0 } else { 1 };
+            cur_row[j + 1] = (prev_row[j + 1] + 1).min(cur_row[j] + 1).min(prev_row[j] + cost);
+        }
+
+        std::mem::swap(&mut prev_row, &mut cur_row);
+    }
+
+    prev_row[b.len()]
+}
+
+struct BkNode {
+    name: String,
+    // Keyed by edit distance from this node to the child.
+    children: Vec<(u32, BkNode)>,
+}
+
+impl BkNode {
+    fn insert(&mut self, name: String) {
+        let dist = edit_distance(&self.name, &name);
+        if dist == 0 {
+            return;
+        }
+
+        if let Some((_, child)) = self.children.iter_mut().find(|(d, _)| *d == dist) {
+            child.insert(name);
+        } else {
+            self.children.push((
+                dist,
+                BkNode {
+                    name,
+                    children: Vec::new(),
+                },
+            ));
+        }
+    }
+
+    fn query<'a>(&'a self, target: &str, tolerance: u32, out: &mut Vec<&'a str>) {
+        let dist = edit_distance(&self.name, target);
+        if dist <= tolerance {
+            out.push(&self.name);
+        }
+
+        let lower = dist.saturating_sub(tolerance);
+        let upper = dist + tolerance;
+        for (child_dist, child) in &self.children {
+            if *child_dist >= lower && *child_dist <= upper {
+                child.query(target, tolerance, out);
+            }
+        }
+    }
+}
+
+/// A BK-tree over article names, keyed by Levenshtein edit distance, supporting
+/// typo-tolerant lookups in sub-linear time via the triangle-inequality pruning
+/// on child edge labels.
+#[derive(Default, Serialize, Deserialize)]
+pub struct BkTree {
+    root: Option<BkNode>,
+}
+
+// `BkNode` isn't serde-derived directly above because it has a self-referential
+// shape that's easiest to (de)serialize via a flat list of names; see below.
+impl Serialize for BkNode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.collect_names().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for BkNode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let names = Vec::<String>::deserialize(deserializer)?;
+        let mut iter = names.into_iter();
+        let mut root = BkNode {
+            name: iter.next().ok_or_else(|| serde::de::Error::custom("empty BK-tree"))?,
+            children: Vec::new(),
+        };
+        for name in iter {
+            root.insert(name);
+        }
+        Ok(root)
+    }
+}
+
+impl BkNode {
+    fn collect_names(&self) -> Vec<String> {
+        let mut out = vec![self.name.clone()];
+        for (_, child) in &self.children {
+            out.extend(child.collect_names());
+        }
+        out
+    }
+}
+
+impl BkTree {
+    pub fn insert(&mut self, name: String) {
+        match &mut self.root {
+            Some(root) => root.insert(name),
+            None => {
+                self.root = Some(BkNode {
+                    name,
+                    children: Vec::new(),
+                })
+            }
+        }
+    }
+
+    /// Returns up to `limit` article names within edit distance `tolerance` of `query`.
+    pub fn search(&self, query: &str, tolerance: u32, limit: usize) -> Vec<&str> {
+        let mut out = Vec::new();
+        if let Some(root) = &self.root {
+            root.query(query, tolerance, &mut out);
+        }
+        out.truncate(limit);
+        out
+    }
+}